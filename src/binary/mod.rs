@@ -12,6 +12,14 @@ pub enum BinaryError {
     SyntaxError(String, String),
     #[error("Binary data version mismatch: expected {0}, got {1}")]
     IncorrectVersion(String, String),
+    #[error("Binary data format version {1} is incompatible with {0}")]
+    IncompatibleVersion(String, String),
+    #[error("File table checksum mismatch: expected {0}, got {1}")]
+    TableChecksumMismatch(String, String),
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
 }
 
 impl From<std::io::Error> for BinaryError {
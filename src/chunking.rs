@@ -0,0 +1,96 @@
+use std::sync::OnceLock;
+
+pub const MIN_CHUNK_SIZE: usize = 16 * 1024;
+pub const MAX_CHUNK_SIZE: usize = 256 * 1024;
+const MASK: u64 = 64 * 1024 - 1;
+
+fn gear_table() -> &'static [u64; 256] {
+    static TABLE: OnceLock<[u64; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u64; 256];
+        let mut seed: u64 = 0x9E3779B97F4A7C15;
+        for entry in table.iter_mut() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            *entry = seed;
+        }
+        table
+    })
+}
+
+// Gear/Buzhash-style rolling hash, cuts where the fingerprint matches MASK once
+// a chunk has reached MIN_CHUNK_SIZE, and always at MAX_CHUNK_SIZE.
+pub fn split(data: &[u8]) -> Vec<&[u8]> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    let table = gear_table();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut hash: u64 = 0;
+
+    for i in 0..data.len() {
+        hash = (hash << 1).wrapping_add(table[data[i] as usize]);
+        let len = i - start + 1;
+        if (len >= MIN_CHUNK_SIZE && hash & MASK == MASK) || len >= MAX_CHUNK_SIZE {
+            chunks.push(&data[start..=i]);
+            start = i + 1;
+            hash = 0;
+        }
+    }
+    if start < data.len() {
+        chunks.push(&data[start..]);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_has_no_chunks() {
+        assert!(split(&[]).is_empty());
+    }
+
+    #[test]
+    fn input_below_min_chunk_size_is_one_chunk() {
+        let data = vec![7u8; MIN_CHUNK_SIZE - 1];
+        let chunks = split(&data);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0], data.as_slice());
+    }
+
+    #[test]
+    fn chunks_reassemble_into_the_original_data() {
+        let mut data = Vec::with_capacity(MAX_CHUNK_SIZE * 4);
+        let mut seed: u32 = 1;
+        for _ in 0..data.capacity() {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            data.push((seed >> 16) as u8);
+        }
+
+        let chunks = split(&data);
+        let reassembled: Vec<u8> = chunks.iter().copied().flatten().copied().collect();
+        assert_eq!(reassembled, data);
+    }
+
+    #[test]
+    fn every_chunk_but_the_last_is_within_bounds() {
+        let mut data = Vec::with_capacity(MAX_CHUNK_SIZE * 4);
+        let mut seed: u32 = 42;
+        for _ in 0..data.capacity() {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            data.push((seed >> 16) as u8);
+        }
+
+        let chunks = split(&data);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.len() >= MIN_CHUNK_SIZE);
+            assert!(chunk.len() <= MAX_CHUNK_SIZE);
+        }
+    }
+}
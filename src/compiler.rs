@@ -1,7 +1,15 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
-use crate::{write, Binary, File, Format};
+use crate::{
+    chunking, compress, write, Binary, Chunk, Compression, CompressionError, File,
+    Format,
+};
+
+type SourceMetadata = (Option<u32>, Option<u64>, Vec<(String, Vec<u8>)>);
 
 #[derive(Debug, Error)]
 pub enum CompilerBuilderError {
@@ -14,6 +22,8 @@ pub enum CompilerBuilderError {
 pub struct CompilerBuilder {
     sources: Option<PathBuf>,
     binary: Option<PathBuf>,
+    compression: Compression,
+    incremental: bool,
 }
 
 impl Default for CompilerBuilder {
@@ -21,6 +31,8 @@ impl Default for CompilerBuilder {
         CompilerBuilder {
             sources: None,
             binary: None,
+            compression: Compression::Zstd,
+            incremental: false,
         }
     }
 }
@@ -36,14 +48,44 @@ impl CompilerBuilder {
         self
     }
 
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Every source is still read and hashed on each run (the hash is what
+    /// detects a change despite a preserved mtime), but sources whose hash
+    /// and mtime both match the previous archive skip CDC chunking and
+    /// recompression, and their chunks are copied forward without
+    /// decompressing them to rebuild the dedup table. The output archive is
+    /// still rewritten from scratch each run; only this per-file CPU work
+    /// scales with what actually changed.
+    pub fn incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
     pub fn build(self) -> Result<Compiler, CompilerBuilderError> {
         let sources = self.sources.ok_or(CompilerBuilderError::NoSourcesPath)?;
         let binary = self.binary.ok_or(CompilerBuilderError::NoBinaryPath)?;
 
-        Ok(Compiler { sources, binary })
+        Ok(Compiler {
+            sources,
+            binary,
+            compression: self.compression,
+            incremental: self.incremental,
+        })
     }
 }
 
+#[derive(Debug, Default)]
+pub struct CompileReport {
+    pub added: Vec<String>,
+    pub changed: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: Vec<String>,
+}
+
 #[derive(Debug, Error)]
 pub enum CompilerError {
     #[error("The specified sources path does not exist")]
@@ -60,11 +102,15 @@ pub enum CompilerError {
     FailedOpenBinary(std::io::Error, PathBuf),
     #[error("Failed to write contents: {0}")]
     FailedWriteContents(std::io::Error),
+    #[error("Failed to compress {1}: {0}")]
+    FailedCompress(CompressionError, PathBuf),
 }
 
 pub struct Compiler {
     sources: PathBuf,
     binary: PathBuf,
+    compression: Compression,
+    incremental: bool,
 }
 
 impl Compiler {
@@ -72,62 +118,177 @@ impl Compiler {
         CompilerBuilder::default()
     }
 
-    pub fn compile(&self) -> Result<(), CompilerError> {
+    pub fn compile(&self) -> Result<CompileReport, CompilerError> {
+        let old = self.incremental.then(|| self.read_old()).flatten();
+
         self.check_files_exist()?;
         let mut file = std::fs::OpenOptions::new()
-            .write(true)
             .append(true)
             .open(self.binary.clone())
             .map_err(|e| CompilerError::FailedOpenBinary(e, self.binary.clone()))?;
         let mut writer = std::io::BufWriter::new(&mut file);
 
+        let old_files: HashMap<String, &File> = old
+            .as_ref()
+            .map(|(format, _)| format.files.iter().map(|f| (f.path.clone(), f)).collect())
+            .unwrap_or_default();
+        let old_chunks: &[Chunk] = old.as_ref().map(|(format, _)| format.chunks.as_slice()).unwrap_or(&[]);
+        let old_payload: &[u8] = old.as_ref().map(|(_, payload)| payload.as_slice()).unwrap_or(&[]);
+
         let mut format = Format::new();
         let sources_raw = self.list_sources()?;
         let mut contents = Vec::new();
+        let mut seen_chunks: HashMap<[u8; 32], u32> = HashMap::new();
+        let mut report = CompileReport::default();
+        let mut seen_paths = std::collections::HashSet::new();
 
         let mut index: u64 = 0;
         for source in &sources_raw {
-            let content = std::fs::read_to_string(&source)
-                .map_err(|e| CompilerError::FailedReadSource(e, source.clone()))?;
-            let size: u64 = content.len() as u64;
             let path = Self::relative_path(source, &self.sources).ok_or_else(|| {
                 CompilerError::FailedReadSource(
                     std::io::Error::new(std::io::ErrorKind::NotFound, "Path not found"),
                     source.clone(),
                 )
             })?;
-            format.add_file(File::new(path, index, size));
-            contents.push(content);
-            index += size;
+            seen_paths.insert(path.clone());
+            let (mode, mtime, xattrs) = Self::read_metadata(source);
+
+            let content = std::fs::read(source)
+                .map_err(|e| CompilerError::FailedReadSource(e, source.clone()))?;
+            let hash: [u8; 32] = Sha256::digest(&content).into();
+
+            if let Some(old_file) = old_files.get(&path) {
+                let unchanged = mtime.is_some()
+                    && old_file.mtime == mtime
+                    && old_file.hash == hash
+                    && old_file.chunks.iter().all(|chunk_index| {
+                        old_chunks.get(*chunk_index as usize).is_some_and(|chunk| {
+                            chunk.offset.saturating_add(chunk.size) <= old_payload.len() as u64
+                        })
+                    });
+
+                if unchanged {
+                    report.unchanged.push(path.clone());
+                    let mut chunk_indices = Vec::new();
+                    for old_chunk_index in &old_file.chunks {
+                        let old_chunk = &old_chunks[*old_chunk_index as usize];
+                        let digest = old_chunk.content_hash;
+
+                        let chunk_index = if let Some(chunk_index) = seen_chunks.get(&digest) {
+                            *chunk_index
+                        } else {
+                            let compressed = old_payload[old_chunk.offset as usize
+                                ..(old_chunk.offset + old_chunk.size) as usize]
+                                .to_vec();
+                            let size = compressed.len() as u64;
+                            let chunk_index = format.add_chunk(Chunk::new(
+                                index,
+                                size,
+                                old_chunk.uncompressed_size,
+                                old_chunk.compression,
+                                digest,
+                            ));
+                            contents.push(compressed);
+                            index += size;
+                            seen_chunks.insert(digest, chunk_index);
+                            chunk_index
+                        };
+                        chunk_indices.push(chunk_index);
+                    }
+
+                    format.add_file(File::new(
+                        path,
+                        chunk_indices,
+                        old_file.uncompressed_size,
+                        old_file.hash,
+                        mode,
+                        mtime,
+                        xattrs,
+                    ));
+                    continue;
+                }
+                report.changed.push(path.clone());
+            } else {
+                report.added.push(path.clone());
+            }
+
+            let uncompressed_size: u64 = content.len() as u64;
+
+            let mut chunk_indices = Vec::new();
+            for chunk in chunking::split(&content) {
+                let digest: [u8; 32] = Sha256::digest(chunk).into();
+                if let Some(chunk_index) = seen_chunks.get(&digest) {
+                    chunk_indices.push(*chunk_index);
+                    continue;
+                }
+
+                let compressed = compress(chunk, self.compression)
+                    .map_err(|e| CompilerError::FailedCompress(e, source.clone()))?;
+                let size = compressed.len() as u64;
+                let chunk_index = format.add_chunk(Chunk::new(
+                    index,
+                    size,
+                    chunk.len() as u64,
+                    self.compression,
+                    digest,
+                ));
+                contents.push(compressed);
+                index += size;
+                seen_chunks.insert(digest, chunk_index);
+                chunk_indices.push(chunk_index);
+            }
+
+            format.add_file(File::new(
+                path,
+                chunk_indices,
+                uncompressed_size,
+                hash,
+                mode,
+                mtime,
+                xattrs,
+            ));
         }
 
+        for old_path in old_files.keys() {
+            if !seen_paths.contains(old_path) {
+                report.removed.push(old_path.clone());
+            }
+        }
+
+        format.header.table_checksum = format.compute_table_checksum();
         format
             .serialize(&mut writer)
             .map_err(|e| CompilerError::FailedWrite(self.binary.clone(), e.to_string()))?;
 
-        for source in sources_raw {
-            let content = std::fs::read_to_string(&source)
-                .map_err(|e| CompilerError::FailedReadSource(e, source.clone()))?;
-            write::string_raw(&mut writer, &content)
-                .map_err(|e| CompilerError::FailedWriteContents(e))?;
+        for content in contents {
+            write::array_raw(&mut writer, &content).map_err(CompilerError::FailedWriteContents)?;
         }
 
-        Ok(())
+        Ok(report)
+    }
+
+    fn read_old(&self) -> Option<(Format, Vec<u8>)> {
+        let file = std::fs::File::open(&self.binary).ok()?;
+        let mut reader = BufReader::new(file);
+        let format = Format::deserialize(&mut reader).ok()?;
+        format.check().ok()?;
+        let mut payload = Vec::new();
+        reader.read_to_end(&mut payload).ok()?;
+        Some((format, payload))
     }
 
     pub fn check_files_exist(&self) -> Result<(), CompilerError> {
         self.sources
             .exists()
             .then_some(())
-            .ok_or_else(|| CompilerError::SourcesDoNotExist)?;
+            .ok_or(CompilerError::SourcesDoNotExist)?;
 
         let binary_parent = self
             .binary
             .parent()
-            .ok_or_else(|| CompilerError::FailedGetBinaryParent)?;
+            .ok_or(CompilerError::FailedGetBinaryParent)?;
         if !binary_parent.exists() {
-            std::fs::create_dir_all(binary_parent)
-                .map_err(|e| CompilerError::FailedCreateBinary(e))?;
+            std::fs::create_dir_all(binary_parent).map_err(CompilerError::FailedCreateBinary)?;
         }
         if self.binary.exists() {
             std::fs::remove_file(&self.binary)
@@ -144,10 +305,44 @@ impl Compiler {
             return Err(CompilerError::SourcesDoNotExist);
         }
 
-        Ok(Self::list_files(&self.sources)?)
+        Self::list_files(&self.sources)
+    }
+
+    fn read_metadata(source: &Path) -> SourceMetadata {
+        let metadata = std::fs::metadata(source).ok();
+
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            metadata.as_ref().map(|m| m.permissions().mode())
+        };
+        #[cfg(not(unix))]
+        let mode = None;
+
+        let mtime = metadata
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+
+        #[cfg(unix)]
+        let xattrs = xattr::list(source)
+            .map(|names| {
+                names
+                    .filter_map(|name| {
+                        let value = xattr::get(source, &name).ok().flatten()?;
+                        Some((name.to_string_lossy().into_owned(), value))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        #[cfg(not(unix))]
+        let xattrs = Vec::new();
+
+        (mode, mtime, xattrs)
     }
 
-    fn relative_path(source: &PathBuf, base: &PathBuf) -> Option<String> {
+    fn relative_path(source: &Path, base: &Path) -> Option<String> {
         source
             .strip_prefix(base)
             .ok()
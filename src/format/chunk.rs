@@ -0,0 +1,57 @@
+use std::io::{Read, Write};
+
+use crate::{read, write, Binary, BinaryError, Compression};
+
+#[derive(Debug)]
+pub struct Chunk {
+    pub offset: u64,
+    pub size: u64,
+    pub uncompressed_size: u64,
+    pub compression: Compression,
+    pub content_hash: [u8; 32],
+}
+
+impl Chunk {
+    pub fn new(
+        offset: u64,
+        size: u64,
+        uncompressed_size: u64,
+        compression: Compression,
+        content_hash: [u8; 32],
+    ) -> Self {
+        Self {
+            offset,
+            size,
+            uncompressed_size,
+            compression,
+            content_hash,
+        }
+    }
+}
+
+impl Binary for Chunk {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), BinaryError> {
+        write::u64(writer, self.offset)?;
+        write::u64(writer, self.size)?;
+        write::u8(writer, self.compression as u8)?;
+        write::u64(writer, self.uncompressed_size)?;
+        write::array_raw(writer, &self.content_hash)?;
+        Ok(())
+    }
+
+    fn deserialize<R: Read>(reader: &mut R) -> Result<Self, BinaryError> {
+        let offset = read::u64(reader)?;
+        let size = read::u64(reader)?;
+        let compression = Compression::from_tag(read::u8(reader)?)?;
+        let uncompressed_size = read::u64(reader)?;
+        let mut content_hash = [0u8; 32];
+        read::exact(reader, &mut content_hash)?;
+        Ok(Chunk {
+            offset,
+            size,
+            uncompressed_size,
+            compression,
+            content_hash,
+        })
+    }
+}
@@ -0,0 +1,169 @@
+use thiserror::Error;
+
+use crate::BinaryError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None = 0,
+    Zstd = 1,
+    Bzip2 = 2,
+    Lzma = 3,
+}
+
+impl Compression {
+    pub fn from_tag(tag: u8) -> Result<Self, BinaryError> {
+        match tag {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Zstd),
+            2 => Ok(Compression::Bzip2),
+            3 => Ok(Compression::Lzma),
+            _ => Err(BinaryError::SyntaxError(
+                "Invalid compression tag".into(),
+                tag.to_string(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CompressionError {
+    #[error("Compression codec {0:?} is not enabled in this build")]
+    CodecDisabled(Compression),
+    #[error("Failed to compress data: {0}")]
+    EncodeFailed(std::io::Error),
+    #[error("Failed to decompress data: {0}")]
+    DecodeFailed(std::io::Error),
+}
+
+pub fn compress(data: &[u8], method: Compression) -> Result<Vec<u8>, CompressionError> {
+    match method {
+        Compression::None => Ok(data.to_vec()),
+        Compression::Zstd => {
+            #[cfg(feature = "compress-zstd")]
+            {
+                zstd::stream::encode_all(data, 0).map_err(CompressionError::EncodeFailed)
+            }
+            #[cfg(not(feature = "compress-zstd"))]
+            {
+                Err(CompressionError::CodecDisabled(method))
+            }
+        }
+        Compression::Bzip2 => {
+            #[cfg(feature = "compress-bzip2")]
+            {
+                use std::io::Write;
+                let mut encoder =
+                    bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder
+                    .write_all(data)
+                    .map_err(CompressionError::EncodeFailed)?;
+                encoder.finish().map_err(CompressionError::EncodeFailed)
+            }
+            #[cfg(not(feature = "compress-bzip2"))]
+            {
+                Err(CompressionError::CodecDisabled(method))
+            }
+        }
+        Compression::Lzma => {
+            #[cfg(feature = "compress-lzma")]
+            {
+                let mut out = Vec::new();
+                lzma_rs::lzma_compress(&mut std::io::Cursor::new(data), &mut out)
+                    .map_err(|e| CompressionError::EncodeFailed(std::io::Error::other(e)))?;
+                Ok(out)
+            }
+            #[cfg(not(feature = "compress-lzma"))]
+            {
+                Err(CompressionError::CodecDisabled(method))
+            }
+        }
+    }
+}
+
+pub fn decompress(
+    data: &[u8],
+    method: Compression,
+    uncompressed_size: u64,
+) -> Result<Vec<u8>, CompressionError> {
+    match method {
+        Compression::None => Ok(data.to_vec()),
+        Compression::Zstd => {
+            #[cfg(feature = "compress-zstd")]
+            {
+                zstd::stream::decode_all(data).map_err(CompressionError::DecodeFailed)
+            }
+            #[cfg(not(feature = "compress-zstd"))]
+            {
+                let _ = uncompressed_size;
+                Err(CompressionError::CodecDisabled(method))
+            }
+        }
+        Compression::Bzip2 => {
+            #[cfg(feature = "compress-bzip2")]
+            {
+                use std::io::Read;
+                let mut decoder = bzip2::read::BzDecoder::new(data);
+                let mut out = Vec::with_capacity(uncompressed_size as usize);
+                decoder
+                    .read_to_end(&mut out)
+                    .map_err(CompressionError::DecodeFailed)?;
+                Ok(out)
+            }
+            #[cfg(not(feature = "compress-bzip2"))]
+            {
+                let _ = uncompressed_size;
+                Err(CompressionError::CodecDisabled(method))
+            }
+        }
+        Compression::Lzma => {
+            #[cfg(feature = "compress-lzma")]
+            {
+                let mut out = Vec::new();
+                lzma_rs::lzma_decompress(&mut std::io::Cursor::new(data), &mut out)
+                    .map_err(|e| CompressionError::DecodeFailed(std::io::Error::other(e)))?;
+                Ok(out)
+            }
+            #[cfg(not(feature = "compress-lzma"))]
+            {
+                let _ = uncompressed_size;
+                Err(CompressionError::CodecDisabled(method))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(method: Compression) {
+        let data = b"the quick brown fox jumps over the lazy dog".repeat(64);
+        let compressed = compress(&data, method).expect("compress should succeed");
+        let decompressed =
+            decompress(&compressed, method, data.len() as u64).expect("decompress should succeed");
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn none_round_trips() {
+        round_trip(Compression::None);
+    }
+
+    #[test]
+    #[cfg(feature = "compress-zstd")]
+    fn zstd_round_trips() {
+        round_trip(Compression::Zstd);
+    }
+
+    #[test]
+    #[cfg(feature = "compress-bzip2")]
+    fn bzip2_round_trips() {
+        round_trip(Compression::Bzip2);
+    }
+
+    #[test]
+    #[cfg(feature = "compress-lzma")]
+    fn lzma_round_trips() {
+        round_trip(Compression::Lzma);
+    }
+}
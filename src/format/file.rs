@@ -2,31 +2,113 @@ use std::io::{Read, Write};
 
 use crate::{read, write, Binary, BinaryError};
 
+const METADATA_HAS_MODE: u8 = 0b0000_0001;
+const METADATA_HAS_MTIME: u8 = 0b0000_0010;
+
 #[derive(Debug)]
 pub struct File {
     pub path: String,
-    pub offset: u64,
-    pub size: u64,
+    pub chunks: Vec<u32>,
+    pub uncompressed_size: u64,
+    pub hash: [u8; 32],
+    pub mode: Option<u32>,
+    pub mtime: Option<u64>,
+    pub xattrs: Vec<(String, Vec<u8>)>,
 }
 
 impl File {
-    pub fn new(path: String, offset: u64, size: u64) -> Self {
-        Self { path, offset, size }
+    pub fn new(
+        path: String,
+        chunks: Vec<u32>,
+        uncompressed_size: u64,
+        hash: [u8; 32],
+        mode: Option<u32>,
+        mtime: Option<u64>,
+        xattrs: Vec<(String, Vec<u8>)>,
+    ) -> Self {
+        Self {
+            path,
+            chunks,
+            uncompressed_size,
+            hash,
+            mode,
+            mtime,
+            xattrs,
+        }
     }
 }
 
 impl Binary for File {
     fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), BinaryError> {
-        write::u64(writer, self.offset)?;
-        write::u64(writer, self.size)?;
+        write::u64(writer, self.uncompressed_size)?;
+        write::u32(writer, self.chunks.len() as u32)?;
+        for chunk in &self.chunks {
+            write::u32(writer, *chunk)?;
+        }
+        write::array_raw(writer, &self.hash)?;
+
+        let mut flags = 0u8;
+        if self.mode.is_some() {
+            flags |= METADATA_HAS_MODE;
+        }
+        if self.mtime.is_some() {
+            flags |= METADATA_HAS_MTIME;
+        }
+        write::u8(writer, flags)?;
+        if let Some(mode) = self.mode {
+            write::u32(writer, mode)?;
+        }
+        if let Some(mtime) = self.mtime {
+            write::u64(writer, mtime)?;
+        }
+        write::u32(writer, self.xattrs.len() as u32)?;
+        for (name, value) in &self.xattrs {
+            write::string(writer, name)?;
+            write::array(writer, value)?;
+        }
+
         write::string(writer, &self.path)?;
         Ok(())
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, BinaryError> {
-        let offset = read::u64(reader)?;
-        let size = read::u64(reader)?;
+        let uncompressed_size = read::u64(reader)?;
+        let chunk_count = read::u32(reader)? as usize;
+        let mut chunks = Vec::with_capacity(chunk_count);
+        for _ in 0..chunk_count {
+            chunks.push(read::u32(reader)?);
+        }
+        let mut hash = [0u8; 32];
+        read::exact(reader, &mut hash)?;
+
+        let flags = read::u8(reader)?;
+        let mode = if flags & METADATA_HAS_MODE != 0 {
+            Some(read::u32(reader)?)
+        } else {
+            None
+        };
+        let mtime = if flags & METADATA_HAS_MTIME != 0 {
+            Some(read::u64(reader)?)
+        } else {
+            None
+        };
+        let xattr_count = read::u32(reader)? as usize;
+        let mut xattrs = Vec::with_capacity(xattr_count);
+        for _ in 0..xattr_count {
+            let name = read::string(reader)?;
+            let value = read::array(reader)?;
+            xattrs.push((name, value));
+        }
+
         let path = read::string(reader)?;
-        Ok(File { path, offset, size })
+        Ok(File {
+            path,
+            chunks,
+            uncompressed_size,
+            hash,
+            mode,
+            mtime,
+            xattrs,
+        })
     }
 }
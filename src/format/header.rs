@@ -5,6 +5,17 @@ use crate::{read, write, Binary, BinaryError};
 const MAGIC: &str = "RASS";
 const GITHUB: &str = "github.com/levilovie/rdss";
 
+// On-disk format version, independent of CARGO_PKG_VERSION.
+// Bump the major version whenever the binary layout changes in a way that
+// would misalign a reader built against the previous layout (e.g. a field
+// added to Chunk); bump minor for additions that readers of the same major
+// version handle but older readers do not (`check_version` rejects an
+// archive whose minor version is newer than the reader's), and patch for
+// changes that every reader of the same major version can still parse.
+const FORMAT_VERSION_MAJOR: u8 = 2;
+const FORMAT_VERSION_MINOR: u8 = 0;
+const FORMAT_VERSION_PATCH: u8 = 0;
+
 #[derive(Debug)]
 pub struct Header {
     pub magic: String,
@@ -12,46 +23,83 @@ pub struct Header {
     pub version_major: u8,
     pub version_minor: u8,
     pub version_patch: u8,
+    pub table_checksum: [u8; 32],
 }
 
 impl Header {
     pub fn new() -> Self {
-        let version = env!("CARGO_PKG_VERSION")
-            .split('.')
-            .map(|s| s.parse().unwrap_or(0))
-            .collect::<Vec<u8>>();
-
         Header {
             magic: MAGIC.to_string(),
             github: GITHUB.to_string(),
-            version_major: *version.get(0).unwrap_or(&0),
-            version_minor: *version.get(1).unwrap_or(&0),
-            version_patch: *version.get(2).unwrap_or(&0),
+            version_major: FORMAT_VERSION_MAJOR,
+            version_minor: FORMAT_VERSION_MINOR,
+            version_patch: FORMAT_VERSION_PATCH,
+            table_checksum: [0u8; 32],
         }
     }
 
+    fn current_version_string() -> String {
+        format!(
+            "{}.{}.{}",
+            FORMAT_VERSION_MAJOR, FORMAT_VERSION_MINOR, FORMAT_VERSION_PATCH
+        )
+    }
+
+    fn version_string(&self) -> String {
+        format!(
+            "{}.{}.{}",
+            self.version_major, self.version_minor, self.version_patch
+        )
+    }
+
+    /// Accepts any archive whose major version matches and whose minor version
+    /// is no newer than this build's, regardless of patch version.
     pub fn check_version(&self) -> Result<(), BinaryError> {
-        let version = env!("CARGO_PKG_VERSION")
-            .split('.')
-            .map(|s| s.parse().unwrap_or(0))
-            .collect::<Vec<u8>>();
-        let version_major = *version.get(0).unwrap_or(&0);
-        let version_minor = *version.get(1).unwrap_or(&0);
-        let version_patch = *version.get(2).unwrap_or(&0);
-        if self.version_major != version_major
-            || self.version_minor != version_minor
-            || self.version_patch != version_patch
+        if self.version_major != FORMAT_VERSION_MAJOR || self.version_minor > FORMAT_VERSION_MINOR
+        {
+            return Err(BinaryError::IncompatibleVersion(
+                Self::current_version_string(),
+                self.version_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Requires the archive's format version to match this build's exactly.
+    pub fn check_version_strict(&self) -> Result<(), BinaryError> {
+        if self.version_major != FORMAT_VERSION_MAJOR
+            || self.version_minor != FORMAT_VERSION_MINOR
+            || self.version_patch != FORMAT_VERSION_PATCH
         {
             return Err(BinaryError::IncorrectVersion(
-                format!("{}.{}.{}", version_major, version_minor, version_patch),
-                format!(
-                    "{}.{}.{}",
-                    self.version_major, self.version_minor, self.version_patch
-                ),
+                Self::current_version_string(),
+                self.version_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_identity(&self) -> Result<(), BinaryError> {
+        if self.magic != MAGIC {
+            return Err(BinaryError::SyntaxError(
+                "Invalid magic number".into(),
+                format!("Expected '{}', got '{}'", MAGIC, self.magic),
+            ));
+        }
+        if self.github != GITHUB {
+            return Err(BinaryError::SyntaxError(
+                "Invalid GitHub link".into(),
+                format!("Expected '{}', got '{}'", GITHUB, self.github),
             ));
         }
         Ok(())
     }
+
+    /// Like `check`, but requires the archive's format version to match exactly.
+    pub fn check_strict(&self) -> Result<(), BinaryError> {
+        self.check_identity()?;
+        self.check_version_strict()
+    }
 }
 
 impl Binary for Header {
@@ -62,6 +110,7 @@ impl Binary for Header {
         write::u8(writer, self.version_major)?;
         write::u8(writer, self.version_minor)?;
         write::u8(writer, self.version_patch)?;
+        write::array_raw(writer, &self.table_checksum)?;
         Ok(())
     }
 
@@ -81,6 +130,10 @@ impl Binary for Header {
         let version_patch = read::u8(reader).map_err(|e| {
             BinaryError::SyntaxError("Failed to read patch version".into(), e.to_string())
         })?;
+        let mut table_checksum = [0u8; 32];
+        read::exact(reader, &mut table_checksum).map_err(|e| {
+            BinaryError::SyntaxError("Failed to read table checksum".into(), e.to_string())
+        })?;
 
         Ok(Header {
             magic,
@@ -88,22 +141,12 @@ impl Binary for Header {
             version_major,
             version_minor,
             version_patch,
+            table_checksum,
         })
     }
 
     fn check(&self) -> Result<(), BinaryError> {
-        if self.magic != MAGIC {
-            return Err(BinaryError::SyntaxError(
-                "Invalid magic number".into(),
-                format!("Expected '{}', got '{}'", MAGIC, self.magic),
-            ));
-        }
-        if self.github != GITHUB {
-            return Err(BinaryError::SyntaxError(
-                "Invalid GitHub link".into(),
-                format!("Expected '{}', got '{}'", GITHUB, self.github),
-            ));
-        }
+        self.check_identity()?;
         self.check_version()
     }
 }
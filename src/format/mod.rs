@@ -1,27 +1,46 @@
+mod chunk;
+mod compression;
 mod file;
 mod header;
 
+pub use chunk::Chunk;
+pub use compression::{compress, decompress, Compression, CompressionError};
 pub use file::File;
 pub use header::Header;
 
 use std::io::{Read, Write};
 
-use crate::{read, write, Binary, BinaryError};
+use sha2::{Digest, Sha256};
+
+use crate::{hex, read, write, Binary, BinaryError};
 
 #[derive(Debug)]
 pub struct Format {
     pub header: Header,
+    pub chunks: Vec<Chunk>,
     pub files: Vec<File>,
 }
 
+impl Default for Format {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Format {
     pub fn new() -> Self {
         Self {
             header: Header::new(),
+            chunks: Vec::new(),
             files: Vec::new(),
         }
     }
 
+    pub fn add_chunk(&mut self, chunk: Chunk) -> u32 {
+        self.chunks.push(chunk);
+        (self.chunks.len() - 1) as u32
+    }
+
     pub fn add_file(&mut self, file: File) {
         self.files.push(file);
     }
@@ -29,11 +48,19 @@ impl Format {
     pub fn add_files(&mut self, files: Vec<File>) {
         self.files.extend(files);
     }
-}
 
-impl Binary for Format {
-    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), BinaryError> {
-        self.header.serialize(writer)?;
+    pub fn compute_table_checksum(&self) -> [u8; 32] {
+        let mut buffer = Vec::new();
+        self.serialize_tables(&mut buffer)
+            .expect("writing to an in-memory buffer cannot fail");
+        Sha256::digest(&buffer).into()
+    }
+
+    fn serialize_tables<W: Write>(&self, writer: &mut W) -> Result<(), BinaryError> {
+        write::u32(writer, self.chunks.len() as u32)?;
+        for chunk in &self.chunks {
+            chunk.serialize(writer)?;
+        }
 
         write::u32(writer, self.files.len() as u32)?;
         for file in &self.files {
@@ -42,19 +69,53 @@ impl Binary for Format {
         Ok(())
     }
 
+    /// Like `check`, but requires the archive's format version to match exactly.
+    pub fn check_strict(&self) -> Result<(), BinaryError> {
+        self.header.check_strict()?;
+        self.check_table_checksum()
+    }
+
+    fn check_table_checksum(&self) -> Result<(), BinaryError> {
+        let computed = self.compute_table_checksum();
+        if computed != self.header.table_checksum {
+            return Err(BinaryError::TableChecksumMismatch(
+                hex(&self.header.table_checksum),
+                hex(&computed),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Binary for Format {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<(), BinaryError> {
+        self.header.serialize(writer)?;
+        self.serialize_tables(writer)
+    }
+
     fn deserialize<R: Read>(reader: &mut R) -> Result<Self, BinaryError> {
         let header = Header::deserialize(reader)?;
 
+        let mut chunks = Vec::new();
+        let chunk_count = read::u32(reader)? as usize;
+        for _ in 0..chunk_count {
+            chunks.push(Chunk::deserialize(reader)?);
+        }
+
         let mut files = Vec::new();
         let file_count = read::u32(reader)? as usize;
         for _ in 0..file_count {
             files.push(File::deserialize(reader)?);
         }
-        Ok(Format { header, files })
+        Ok(Format {
+            header,
+            chunks,
+            files,
+        })
     }
 
     fn check(&self) -> Result<(), BinaryError> {
         self.header.check()?;
-        Ok(())
+        self.check_table_checksum()
     }
 }
@@ -1,9 +1,10 @@
 mod binary;
+mod chunking;
 mod compiler;
 mod format;
 mod loader;
 
-pub use binary::{read, write, Binary, BinaryError};
-pub use compiler::{Compiler, CompilerBuilder, CompilerBuilderError, CompilerError};
-pub use format::{File, Format};
-pub use loader::{Loader, LoaderError};
+pub use binary::{hex, read, write, Binary, BinaryError};
+pub use compiler::{CompileReport, Compiler, CompilerBuilder, CompilerBuilderError, CompilerError};
+pub use format::{compress, decompress, Chunk, Compression, CompressionError, File, Format};
+pub use loader::{Loader, LoaderError, Metadata};
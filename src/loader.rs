@@ -1,11 +1,12 @@
 use std::{
     collections::HashMap,
-    io::{BufReader, Seek},
-    path::PathBuf,
+    io::{BufReader, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
 };
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
-use crate::{read, Binary, BinaryError, Format};
+use crate::{decompress, hex, read, Binary, BinaryError, Chunk, CompressionError, Format};
 
 #[derive(Debug, Error)]
 pub enum LoaderError {
@@ -17,12 +18,40 @@ pub enum LoaderError {
     DeserializationFailed(#[from] BinaryError),
     #[error("Failed to read the binary file: {0}")]
     ReadError(#[from] std::io::Error),
+    #[error("Failed to decompress {0}: {1}")]
+    DecompressionFailed(String, CompressionError),
+    #[error("Integrity check failed for {path}: expected {expected}, got {got}")]
+    IntegrityMismatch {
+        path: String,
+        expected: String,
+        got: String,
+    },
+    #[error("Loader has not been loaded yet, call load() first")]
+    NotLoaded,
+}
+
+struct FileEntry {
+    chunks: Vec<u32>,
+    uncompressed_size: u64,
+    hash: [u8; 32],
+    mode: Option<u32>,
+    mtime: Option<u64>,
+    xattrs: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub mode: Option<u32>,
+    pub mtime: Option<u64>,
+    pub xattrs: Vec<(String, Vec<u8>)>,
 }
 
 pub struct Loader {
     binary: PathBuf,
-    files: HashMap<String, (u64, u64)>,
+    files: HashMap<String, FileEntry>,
+    chunks: Vec<Chunk>,
     start: u64,
+    reader: Option<BufReader<std::fs::File>>,
 }
 
 impl Loader {
@@ -30,7 +59,9 @@ impl Loader {
         Loader {
             binary: binary.into(),
             files: HashMap::new(),
+            chunks: Vec::new(),
             start: 0,
+            reader: None,
         }
     }
 
@@ -39,6 +70,16 @@ impl Loader {
     }
 
     pub fn load(&mut self) -> Result<(), LoaderError> {
+        self.load_impl(false)
+    }
+
+    /// Like `load`, but requires the archive's format version to match this
+    /// build's exactly instead of accepting compatible older minor versions.
+    pub fn load_strict(&mut self) -> Result<(), LoaderError> {
+        self.load_impl(true)
+    }
+
+    fn load_impl(&mut self, strict: bool) -> Result<(), LoaderError> {
         if !self.binary.exists() {
             return Err(LoaderError::BinaryDoesNotExist(self.binary.clone()));
         }
@@ -48,37 +89,165 @@ impl Loader {
 
         let format =
             Format::deserialize(&mut reader).map_err(LoaderError::DeserializationFailed)?;
+        if strict {
+            format.check_strict()
+        } else {
+            format.check()
+        }
+        .map_err(LoaderError::DeserializationFailed)?;
 
+        self.chunks = format.chunks;
         for file in format.files {
-            println!("Found file: {:#?}", file);
-            self.files
-                .insert(file.path.clone(), (file.offset, file.size));
+            self.files.insert(
+                file.path.clone(),
+                FileEntry {
+                    chunks: file.chunks,
+                    uncompressed_size: file.uncompressed_size,
+                    hash: file.hash,
+                    mode: file.mode,
+                    mtime: file.mtime,
+                    xattrs: file.xattrs,
+                },
+            );
         }
 
         self.start = reader.stream_position().map_err(LoaderError::ReadError)?;
+        self.reader = Some(reader);
 
         Ok(())
     }
 
-    pub fn read_raw(&mut self, path: &str) -> Result<Vec<u8>, LoaderError> {
-        if let Some((offset, size)) = self.files.get(path) {
-            let file = std::fs::File::open(&self.binary)
-                .map_err(|_| LoaderError::BinaryDoesNotExist(self.binary.clone()))?;
-            let mut reader = BufReader::new(file);
-
-            read::skip(&mut reader, self.start + offset).map_err(LoaderError::ReadError)?;
-            let mut buffer = vec![0; *size as usize];
-            read::exact(&mut reader, &mut buffer).map_err(LoaderError::ReadError)?;
-            Ok(buffer)
-        } else {
-            Err(LoaderError::FileNotFound(path.to_string()))
+    fn for_each_chunk(
+        &mut self,
+        path: &str,
+        mut on_chunk: impl FnMut(Vec<u8>) -> Result<(), LoaderError>,
+    ) -> Result<(), LoaderError> {
+        let chunk_indices = {
+            let entry = self
+                .files
+                .get(path)
+                .ok_or_else(|| LoaderError::FileNotFound(path.to_string()))?;
+            entry.chunks.clone()
+        };
+
+        for chunk_index in chunk_indices {
+            let (offset, size, compression, chunk_uncompressed_size) = {
+                let chunk = self
+                    .chunks
+                    .get(chunk_index as usize)
+                    .ok_or_else(|| LoaderError::FileNotFound(path.to_string()))?;
+                (
+                    self.start + chunk.offset,
+                    chunk.size as usize,
+                    chunk.compression,
+                    chunk.uncompressed_size,
+                )
+            };
+
+            let reader = self.reader.as_mut().ok_or(LoaderError::NotLoaded)?;
+            reader
+                .seek(SeekFrom::Start(offset))
+                .map_err(LoaderError::ReadError)?;
+            let mut chunk_buffer = vec![0; size];
+            read::exact(reader, &mut chunk_buffer).map_err(LoaderError::ReadError)?;
+
+            let decompressed = decompress(&chunk_buffer, compression, chunk_uncompressed_size)
+                .map_err(|e| LoaderError::DecompressionFailed(path.to_string(), e))?;
+            on_chunk(decompressed)?;
         }
+
+        Ok(())
+    }
+
+    pub fn read_raw(&mut self, path: &str) -> Result<Vec<u8>, LoaderError> {
+        let uncompressed_size = self
+            .files
+            .get(path)
+            .ok_or_else(|| LoaderError::FileNotFound(path.to_string()))?
+            .uncompressed_size;
+
+        let mut buffer = Vec::with_capacity(uncompressed_size as usize);
+        self.for_each_chunk(path, |chunk| {
+            buffer.extend_from_slice(&chunk);
+            Ok(())
+        })?;
+
+        Ok(buffer)
+    }
+
+    pub fn read_to<W: Write>(&mut self, path: &str, writer: &mut W) -> Result<(), LoaderError> {
+        self.for_each_chunk(path, |chunk| {
+            writer.write_all(&chunk).map_err(LoaderError::ReadError)
+        })
     }
 
     pub fn read(&mut self, path: &str) -> Result<String, LoaderError> {
         let bytes = self.read_raw(path)?;
-        String::from_utf8(bytes.into()).map_err(|e| {
+        String::from_utf8(bytes).map_err(|e| {
             LoaderError::ReadError(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
         })
     }
+
+    pub fn verify(&mut self, path: &str) -> Result<(), LoaderError> {
+        let expected = self
+            .files
+            .get(path)
+            .ok_or_else(|| LoaderError::FileNotFound(path.to_string()))?
+            .hash;
+
+        let bytes = self.read_raw(path)?;
+        let got: [u8; 32] = Sha256::digest(&bytes).into();
+        if got != expected {
+            return Err(LoaderError::IntegrityMismatch {
+                path: path.to_string(),
+                expected: hex(&expected),
+                got: hex(&got),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn verify_all(&mut self) -> Result<(), LoaderError> {
+        for path in self.files() {
+            self.verify(&path)?;
+        }
+        Ok(())
+    }
+
+    pub fn metadata(&self, path: &str) -> Result<Metadata, LoaderError> {
+        let entry = self
+            .files
+            .get(path)
+            .ok_or_else(|| LoaderError::FileNotFound(path.to_string()))?;
+        Ok(Metadata {
+            mode: entry.mode,
+            mtime: entry.mtime,
+            xattrs: entry.xattrs.clone(),
+        })
+    }
+
+    pub fn extract_to(&mut self, path: &str, dest: impl AsRef<Path>) -> Result<(), LoaderError> {
+        let metadata = self.metadata(path)?;
+        let dest = dest.as_ref();
+
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(LoaderError::ReadError)?;
+        }
+        let mut out = std::fs::File::create(dest).map_err(LoaderError::ReadError)?;
+        self.read_to(path, &mut out)?;
+
+        #[cfg(unix)]
+        if let Some(mode) = metadata.mode {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(dest, std::fs::Permissions::from_mode(mode))
+                .map_err(LoaderError::ReadError)?;
+        }
+
+        if let Some(mtime) = metadata.mtime {
+            let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime);
+            out.set_modified(modified).map_err(LoaderError::ReadError)?;
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use rdss::{Compiler, Compression, Loader};
+
+fn unique_temp_dir(label: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock is before the epoch")
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("rdss-test-{label}-{nanos}"));
+    std::fs::create_dir_all(&dir).expect("failed to create temp dir");
+    dir
+}
+
+#[test]
+fn changed_content_with_preserved_mtime_is_not_served_stale() {
+    let root = unique_temp_dir("incremental-mtime-spoof");
+    let sources = root.join("sources");
+    let binary = root.join("out.rdss");
+    std::fs::create_dir_all(&sources).expect("failed to create sources dir");
+    let source_file = sources.join("hello.txt");
+    std::fs::write(&source_file, b"original content").expect("failed to write source");
+
+    let compiler = Compiler::builder()
+        .from_sources(&sources)
+        .save_to(&binary)
+        .compression(Compression::None)
+        .incremental(true)
+        .build()
+        .expect("failed to build compiler");
+
+    compiler.compile().expect("failed to compile archive");
+
+    let original_modified = std::fs::metadata(&source_file)
+        .expect("failed to stat source")
+        .modified()
+        .expect("failed to read mtime");
+
+    std::fs::write(&source_file, b"tampered content").expect("failed to rewrite source");
+    std::fs::File::open(&source_file)
+        .expect("failed to reopen source")
+        .set_modified(original_modified)
+        .expect("failed to restore mtime");
+
+    let report = compiler
+        .compile()
+        .expect("failed to recompile with preserved mtime");
+
+    assert!(
+        report.changed.iter().any(|path| path == "hello.txt"),
+        "a content change with a preserved mtime must still be reported as changed, got {report:?}"
+    );
+    assert!(!report.unchanged.iter().any(|path| path == "hello.txt"));
+
+    let mut loader = Loader::new(&binary);
+    loader.load().expect("failed to load archive");
+    let contents = loader.read("hello.txt").expect("failed to read hello.txt");
+    assert_eq!(contents, "tampered content");
+
+    std::fs::remove_dir_all(&root).ok();
+}
@@ -0,0 +1,65 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use rdss::{Compiler, Compression, Loader, LoaderError};
+
+fn unique_temp_dir(label: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock is before the epoch")
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!("rdss-test-{label}-{nanos}"));
+    std::fs::create_dir_all(&dir).expect("failed to create temp dir");
+    dir
+}
+
+#[test]
+fn verify_catches_a_corrupted_byte() {
+    let root = unique_temp_dir("verify-catches-corruption");
+    let sources = root.join("sources");
+    let binary = root.join("out.rdss");
+    std::fs::create_dir_all(&sources).expect("failed to create sources dir");
+    std::fs::write(sources.join("hello.txt"), b"hello, world!").expect("failed to write source");
+
+    Compiler::builder()
+        .from_sources(&sources)
+        .save_to(&binary)
+        .compression(Compression::None)
+        .build()
+        .expect("failed to build compiler")
+        .compile()
+        .expect("failed to compile archive");
+
+    let mut loader = Loader::new(&binary);
+    loader.load().expect("failed to load archive");
+    loader
+        .verify("hello.txt")
+        .expect("archive should be intact before corruption");
+    drop(loader);
+
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&binary)
+        .expect("failed to reopen binary for corruption");
+    let len = file.metadata().expect("failed to stat binary").len();
+    file.seek(SeekFrom::Start(len - 1))
+        .expect("failed to seek to last byte");
+    let mut last_byte = [0u8; 1];
+    file.read_exact(&mut last_byte)
+        .expect("failed to read last byte");
+    file.seek(SeekFrom::Start(len - 1))
+        .expect("failed to seek to last byte");
+    file.write_all(&[last_byte[0] ^ 0xFF])
+        .expect("failed to corrupt last byte");
+    drop(file);
+
+    let mut loader = Loader::new(&binary);
+    loader.load().expect("failed to load corrupted archive");
+    match loader.verify("hello.txt") {
+        Err(LoaderError::IntegrityMismatch { path, .. }) => assert_eq!(path, "hello.txt"),
+        other => panic!("expected an integrity mismatch, got {other:?}"),
+    }
+
+    std::fs::remove_dir_all(&root).ok();
+}